@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { open } from "@tauri-apps/plugin-dialog";
 import { CommitListWithGraph } from "./components/CommitListWithGraph";
 import { GraphLegend } from "./components/GraphLegend";
@@ -25,10 +26,23 @@ import {
 import { StashPanel } from "./components/stash";
 import { CommandPalette, createCommands, type CommandActions } from "./components/command-palette";
 import { KeyboardShortcuts } from "./components/keyboard";
+import type { CommitGraphNode, RefDecoration, TagRef } from "./utils/graphLayout";
 import { QuickSearch } from "./components/quick-search";
 import { StashEntry } from "./components/stash/types";
 import { ThemeToggle } from "./components/ThemeToggle";
 
+// Payload of the "remote-progress" event emitted during fetch/pull/push
+interface RemoteProgress {
+  operation: 'fetch' | 'pull' | 'push';
+  stage: string;
+  received_objects: number;
+  total_objects: number;
+  received_bytes: number;
+  indexed_deltas: number;
+  total_deltas: number;
+  message: string;
+}
+
 interface RepoInfo {
   name: string;
   path: string;
@@ -49,11 +63,16 @@ export interface Commit {
     is_remote: boolean;
     is_current: boolean;
   }>;
-  tags: Array<{
-    name: string;
-    is_annotated: boolean;
-    is_remote: boolean;
-  }>;
+  tags: TagRef[];
+  refs: RefDecoration[];
+  graph?: CommitGraphNode | null;
+}
+
+// One page of history; pass next_cursor back to get_commits to load the next one
+interface CommitPage {
+  commits: Commit[];
+  next_cursor: string | null;
+  cancelled: boolean; // Stopped early: commits is partial, next_cursor resumes after it
 }
 
 interface Branch {
@@ -69,11 +88,36 @@ interface Branch {
 
 interface FileChange {
   path: string;
+  old_path: string | null;
   status: string;
   insertions: number;
   deletions: number;
+  is_binary: boolean;
 }
 
+interface DiffStat {
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+}
+
+// Verified lazily by get_commit_signature when a commit is opened
+interface CommitSignature {
+  status: 'unsigned' | 'good' | 'bad' | 'unknown_key' | 'expired';
+  format: 'openpgp' | 'x509' | 'ssh' | null;
+  signer: string | null;
+  key: string | null;
+  details: string | null;
+}
+
+const SIGNATURE_LABELS: Record<CommitSignature['status'], { label: string; color: string }> = {
+  unsigned: { label: 'Not signed', color: 'text-theme-tertiary' },
+  good: { label: 'Good signature', color: 'text-green-400' },
+  bad: { label: 'Bad signature', color: 'text-red-400' },
+  unknown_key: { label: 'Signed with an unknown key', color: 'text-yellow-400' },
+  expired: { label: 'Signed with an expired key', color: 'text-yellow-400' },
+};
+
 // Format date to readable string
 function formatDate(timestamp: number): string {
   const date = new Date(timestamp * 1000);
@@ -109,9 +153,32 @@ function CommitDetailsPanel({
   onClose: () => void;
 }) {
   const [files, setFiles] = useState<FileChange[]>([]);
+  const [stats, setStats] = useState<DiffStat | null>(null);
   const [loadingFiles, setLoadingFiles] = useState(false);
   const [fileError, setFileError] = useState<string | null>(null);
   const [selectedFile, setSelectedFile] = useState<string | null>(null);
+  const [signature, setSignature] = useState<CommitSignature | null>(null);
+
+  // Verify the signature separately, as it runs gpg or ssh-keygen
+  useEffect(() => {
+    setSignature(null);
+    if (!commit) return;
+
+    let cancelled = false;
+    invoke<CommitSignature>("get_commit_signature", {
+      path: repoPath,
+      commitHash: commit.hash,
+    })
+      .then((result) => {
+        if (!cancelled) setSignature(result);
+      })
+      .catch(() => {
+        // Signature status is informational; the rest of the panel still works
+      });
+    return () => {
+      cancelled = true;
+    };
+  }, [commit, repoPath]);
 
   // Load files when commit is selected
   useEffect(() => {
@@ -119,13 +186,21 @@ function CommitDetailsPanel({
       setLoadingFiles(true);
       setFileError(null);
       setSelectedFile(null); // Reset selected file when commit changes
+      setStats(null);
       
-      invoke<FileChange[]>("get_commit_files", {
-        path: repoPath,
-        commitHash: commit.hash,
-      })
-        .then((files) => {
+      Promise.all([
+        invoke<FileChange[]>("get_commit_files", {
+          path: repoPath,
+          commitHash: commit.hash,
+        }),
+        invoke<DiffStat>("get_commit_stats", {
+          path: repoPath,
+          commitHash: commit.hash,
+        }),
+      ])
+        .then(([files, stats]) => {
           setFiles(files);
+          setStats(stats);
         })
         .catch((err) => {
           setFileError(err instanceof Error ? err.message : String(err));
@@ -161,6 +236,16 @@ function CommitDetailsPanel({
                 <div className="font-mono">{commit.hash}</div>
                 <div>{commit.author_name} ({commit.author_email})</div>
                 <div>{formatDate(commit.timestamp)}</div>
+                {signature && (
+                  <div
+                    className={SIGNATURE_LABELS[signature.status].color}
+                    title={[signature.key, signature.details].filter(Boolean).join('\n')}
+                  >
+                    {SIGNATURE_LABELS[signature.status].label}
+                    {signature.format && ` (${signature.format === 'openpgp' ? 'GPG' : signature.format.toUpperCase()})`}
+                    {signature.signer && ` by ${signature.signer}`}
+                  </div>
+                )}
               </div>
             </div>
             <button
@@ -191,6 +276,13 @@ function CommitDetailsPanel({
               {commit.tags.map((tag) => (
                 <div
                   key={tag.name}
+                  title={
+                    tag.is_annotated
+                      ? `${tag.tagger_name ?? 'Unknown'}${tag.tagger_email ? ` <${tag.tagger_email}>` : ''}${
+                          tag.tagger_date ? `, ${new Date(tag.tagger_date * 1000).toLocaleString()}` : ''
+                        }${tag.message ? `\n\n${tag.message}` : ''}`
+                      : 'Lightweight tag'
+                  }
                   className={`px-2 py-1 rounded text-xs font-mono flex items-center gap-1 ${
                     tag.is_remote
                       ? 'bg-cyan-500 bg-opacity-20 border border-cyan-600 text-cyan-300'
@@ -206,10 +298,36 @@ function CommitDetailsPanel({
           </div>
         )}
 
+        {/* Other Refs Section (HEAD, stash, notes, refs/pull/*, ...) */}
+        {commit.refs && commit.refs.length > 0 && (
+          <div className="border-b border-theme-default p-4">
+            <h4 className="text-xs font-semibold text-theme-primary mb-2 uppercase tracking-wider">
+              Refs ({commit.refs.length})
+            </h4>
+            <div className="flex flex-wrap gap-2">
+              {commit.refs.map((ref) => (
+                <div
+                  key={`${ref.kind}-${ref.name}`}
+                  title={ref.full_name}
+                  className="px-2 py-1 rounded text-xs font-mono bg-theme-bg border border-theme-default text-theme-secondary"
+                >
+                  {ref.name}
+                </div>
+              ))}
+            </div>
+          </div>
+        )}
+
         {/* Files List and Diff Section */}
         <div className="p-4">
         <h4 className="text-xs font-semibold text-theme-primary mb-3 uppercase tracking-wider">
           Files Changed ({files.length})
+          {stats && (
+            <span className="ml-2 font-mono normal-case tracking-normal">
+              <span className="text-green-400">+{stats.insertions}</span>{' '}
+              <span className="text-red-400">−{stats.deletions}</span>
+            </span>
+          )}
         </h4>
 
         {loadingFiles && (
@@ -248,9 +366,19 @@ function CommitDetailsPanel({
                   <div className="flex items-center gap-2 mb-1">
                     <span className={statusInfo.color}>{statusInfo.icon}</span>
                     <span className="text-theme-primary">{statusInfo.label}</span>
+                    <span className="ml-auto whitespace-nowrap">
+                      {file.is_binary ? (
+                        <span className="text-theme-tertiary">binary</span>
+                      ) : (
+                        <>
+                          <span className="text-green-400">+{file.insertions}</span>{' '}
+                          <span className="text-red-400">−{file.deletions}</span>
+                        </>
+                      )}
+                    </span>
                   </div>
                   <div className="text-theme-secondary truncate pl-4 break-all">
-                    {file.path}
+                    {file.old_path ? `${file.old_path} → ${file.path}` : file.path}
                   </div>
                 </div>
               );
@@ -444,6 +572,7 @@ function App() {
   const [pullInProgress, setPullInProgress] = useState(false);
   const [pullComplete, setPullComplete] = useState(false);
   const [pullError, setPullError] = useState<string | null>(null);
+  const [pullProgress, setPullProgress] = useState({ current: 0, total: 0, message: '' });
   const [pullConflicts, setPullConflicts] = useState<Array<{ path: string; conflict_type: string }>>([]);
   const [remoteStatus, setRemoteStatus] = useState<{ ahead: number; behind: number; remoteName: string } | null>(null);
   
@@ -452,6 +581,7 @@ function App() {
   const [pushInProgress, setPushInProgress] = useState(false);
   const [pushComplete, setPushComplete] = useState(false);
   const [pushError, setPushError] = useState<string | null>(null);
+  const [pushProgress, setPushProgress] = useState({ current: 0, total: 0, message: '' });
   const [commitsToPush, setCommitsToPush] = useState<Commit[]>([]);
   
   // Interactive Rebase state
@@ -481,6 +611,32 @@ function App() {
     localStorage.setItem('graft-show-stash-sidebar', JSON.stringify(showStashSidebar));
   }, [showStashSidebar]);
 
+  // Stream live fetch/pull/push progress from the backend into the toasts
+  useEffect(() => {
+    const unlisten = listen<RemoteProgress>('remote-progress', (event) => {
+      const p = event.payload;
+      // While resolving deltas, track delta progress instead of object counts
+      const resolving = p.stage === 'resolving_deltas';
+      const progress = {
+        current: resolving ? p.indexed_deltas : p.received_objects,
+        total: resolving ? p.total_deltas : p.total_objects,
+        message: p.message,
+      };
+
+      if (p.operation === 'fetch') {
+        setFetchProgress(progress);
+      } else if (p.operation === 'pull') {
+        setPullProgress(progress);
+      } else {
+        setPushProgress(progress);
+      }
+    });
+
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Detect OS for keyboard shortcut display
   const isMac = navigator.platform.toUpperCase().indexOf('MAC') >= 0;
   const shortcutKey = isMac ? '⌘' : 'Ctrl';
@@ -680,6 +836,11 @@ function App() {
       });
 
       if (selected && typeof selected === "string") {
+        // Release cached handles of the repository we're switching away from
+        if (repoInfo && repoInfo.path !== selected) {
+          invoke("close_repository", { path: repoInfo.path }).catch(() => {});
+        }
+
         // Call Rust backend to open the repo
         const info = await invoke<RepoInfo>("open_repository", {
           path: selected,
@@ -687,19 +848,18 @@ function App() {
         setRepoInfo(info);
 
         // Fetch initial batch of commits from the repository (pagination)
-        const initialBatch = await invoke<Commit[]>("get_commits", {
+        const initialBatch = await invoke<CommitPage>("get_commits", {
           path: selected,
-          offset: 0,
           limit: 1000, // Initial load: 1000 commits for instant feel
         });
         
-        setCommits(initialBatch);
+        setCommits(initialBatch.commits);
 
-        // Check if we got a full batch (meaning there might be more)
-        if (initialBatch.length === 1000) {
+        // A cursor means there are more commits to load
+        if (initialBatch.next_cursor) {
           setHasMoreCommits(true);
           // Start background loading remaining commits
-          loadRemainingCommitsInBackground(selected, 1000, 10000);
+          loadRemainingCommitsInBackground(selected, initialBatch.next_cursor, 10000 - 1000);
         } else {
           setHasMoreCommits(false);
         }
@@ -722,38 +882,35 @@ function App() {
   // Background loading function - loads remaining commits without blocking UI
   const loadRemainingCommitsInBackground = async (
     repoPath: string,
-    startOffset: number,
-    totalTarget: number
+    startCursor: string,
+    remainingTarget: number
   ) => {
     const batchSize = 1000;
-    let currentOffset = startOffset;
+    let cursor: string | null = startCursor;
+    let loaded = 0;
     
     setLoadingMore(true);
     
     try {
-      while (currentOffset < totalTarget) {
+      while (cursor && loaded < remainingTarget) {
         // Small delay to prevent UI blocking
         await new Promise(resolve => setTimeout(resolve, 50));
         
-        const batch = await invoke<Commit[]>("get_commits", {
+        // The cursor resumes the walk where the previous batch stopped
+        const batch: CommitPage = await invoke<CommitPage>("get_commits", {
           path: repoPath,
-          offset: currentOffset,
+          cursor,
           limit: batchSize,
         });
         
-        // If we got fewer commits than requested, we've reached the end
-        if (batch.length === 0) {
-          setHasMoreCommits(false);
-          break;
-        }
-        
         // Append batch to existing commits
-        setCommits(prevCommits => [...prevCommits, ...batch]);
+        setCommits(prevCommits => [...prevCommits, ...batch.commits]);
         
-        currentOffset += batchSize;
+        loaded += batch.commits.length;
+        cursor = batch.next_cursor;
         
-        // If we got fewer than batchSize, we've reached the end
-        if (batch.length < batchSize) {
+        // No cursor means we've reached the end
+        if (!cursor) {
           setHasMoreCommits(false);
           break;
         }
@@ -789,18 +946,17 @@ function App() {
     if (repoInfo) {
       try {
         // Reload with pagination
-        const initialBatch = await invoke<Commit[]>("get_commits", {
+        const initialBatch = await invoke<CommitPage>("get_commits", {
           path: repoInfo.path,
-          offset: 0,
           limit: 1000,
         });
-        setCommits(initialBatch);
+        setCommits(initialBatch.commits);
         setSelectedCommitIndex(-1); // Deselect after refresh
         
         // Start background loading if needed
-        if (initialBatch.length === 1000) {
+        if (initialBatch.next_cursor) {
           setHasMoreCommits(true);
-          loadRemainingCommitsInBackground(repoInfo.path, 1000, 10000);
+          loadRemainingCommitsInBackground(repoInfo.path, initialBatch.next_cursor, 10000 - 1000);
         } else {
           setHasMoreCommits(false);
         }
@@ -821,18 +977,17 @@ function App() {
         setRepoInfo(info);
 
         // Refresh commit list with pagination
-        const initialBatch = await invoke<Commit[]>("get_commits", {
+        const initialBatch = await invoke<CommitPage>("get_commits", {
           path: repoInfo.path,
-          offset: 0,
           limit: 1000,
         });
-        setCommits(initialBatch);
+        setCommits(initialBatch.commits);
         setSelectedCommitIndex(-1);
         
         // Start background loading if needed
-        if (initialBatch.length === 1000) {
+        if (initialBatch.next_cursor) {
           setHasMoreCommits(true);
-          loadRemainingCommitsInBackground(repoInfo.path, 1000, 10000);
+          loadRemainingCommitsInBackground(repoInfo.path, initialBatch.next_cursor, 10000 - 1000);
         } else {
           setHasMoreCommits(false);
         }
@@ -918,6 +1073,7 @@ function App() {
     setPullComplete(false);
     setPullError(null);
     setPullConflicts([]);
+    setPullProgress({ current: 0, total: 0, message: 'Connecting to remote...' });
 
     try {
       const result = await invoke<{
@@ -975,11 +1131,11 @@ function App() {
         // Get commits to push (only if ahead)
         if (status.ahead > 0) {
           // Get the commits that are ahead
-          const allCommits = await invoke<Commit[]>('get_commits', {
+          const page = await invoke<CommitPage>('get_commits', {
             path: repoInfo.path,
             limit: status.ahead,
           });
-          setCommitsToPush(allCommits.slice(0, status.ahead));
+          setCommitsToPush(page.commits.slice(0, status.ahead));
         } else {
           setCommitsToPush([]);
         }
@@ -998,6 +1154,7 @@ function App() {
     setPushInProgress(true);
     setPushComplete(false);
     setPushError(null);
+    setPushProgress({ current: 0, total: 0, message: 'Connecting to remote...' });
 
     try {
       const result = await invoke<{
@@ -1872,11 +2029,13 @@ function App() {
       {(pullInProgress || pullComplete || (pullError && pullConflicts.length === 0)) && (
         <ProgressToast
           operation="pull"
-          stage={pullInProgress ? 'Pulling changes...' : pullComplete ? 'Pull complete' : 'Pull failed'}
-          progress={pullInProgress ? 50 : 100}
+          stage={pullInProgress ? pullProgress.message : pullComplete ? 'Pull complete' : 'Pull failed'}
+          progress={pullInProgress ? (pullProgress.total > 0 ? (pullProgress.current / pullProgress.total) * 100 : 0) : 100}
+          current={pullInProgress && pullProgress.total > 0 ? pullProgress.current : undefined}
+          total={pullInProgress && pullProgress.total > 0 ? pullProgress.total : undefined}
           isComplete={pullComplete}
           isError={!!pullError && pullConflicts.length === 0}
-          message={pullError && pullConflicts.length === 0 ? pullError : pullComplete ? 'Successfully pulled changes' : 'Pulling changes...'}
+          message={pullError && pullConflicts.length === 0 ? pullError : pullComplete ? 'Successfully pulled changes' : pullProgress.message || 'Pulling changes...'}
           onClose={() => {
             setPullComplete(false);
             if (pullConflicts.length === 0) {
@@ -1913,11 +2072,13 @@ function App() {
       {(pushInProgress || pushComplete || pushError) && (
         <ProgressToast
           operation="push"
-          stage={pushInProgress ? 'Pushing changes...' : pushComplete ? 'Push complete' : 'Push failed'}
-          progress={pushInProgress ? 50 : 100}
+          stage={pushInProgress ? pushProgress.message : pushComplete ? 'Push complete' : 'Push failed'}
+          progress={pushInProgress ? (pushProgress.total > 0 ? (pushProgress.current / pushProgress.total) * 100 : 0) : 100}
+          current={pushInProgress && pushProgress.total > 0 ? pushProgress.current : undefined}
+          total={pushInProgress && pushProgress.total > 0 ? pushProgress.total : undefined}
           isComplete={pushComplete}
           isError={!!pushError}
-          message={pushError || (pushComplete ? 'Successfully pushed changes' : 'Pushing changes...')}
+          message={pushError || (pushComplete ? 'Successfully pushed changes' : pushProgress.message || 'Pushing changes...')}
           onClose={() => {
             setPushComplete(false);
             setPushError(null);
@@ -38,11 +38,19 @@ export const CommitGraph: React.FC<CommitGraphProps> = React.memo(({
       const y1 = edge.from.y + 40;
       const x2 = edge.to.x + 10;
       const y2 = edge.to.y + 40;
-      const color = edge.type === 'merge' ? getBranchColor(edge.to.lane) : getBranchColor(edge.from.lane);
+      const color = getBranchColor(edge.color);
       const midY = (y1 + y2) / 2;
-      const pathD = edge.from.lane === edge.to.lane
-        ? `M ${x1} ${y1} L ${x2} ${y2}`
-        : `M ${x1} ${y1} L ${x1} ${midY} L ${x2} ${midY} L ${x2} ${y2}`;
+      let pathD: string;
+      if (edge.lane !== undefined && (edge.lane !== edge.from.lane || edge.lane !== edge.to.lane)) {
+        // Backend routing: step into the edge's lane below the commit, run down
+        // it and step into the parent's lane just above the parent
+        const xl = edge.lane * 40 + 10;
+        pathD = `M ${x1} ${y1} L ${xl} ${y1 + 40} L ${xl} ${y2 - 40} L ${x2} ${y2}`;
+      } else if (edge.from.lane === edge.to.lane) {
+        pathD = `M ${x1} ${y1} L ${x2} ${y2}`;
+      } else {
+        pathD = `M ${x1} ${y1} L ${x1} ${midY} L ${x2} ${midY} L ${x2} ${y2}`;
+      }
 
       return {
         key: `edge-${idx}`,
@@ -82,7 +90,7 @@ export const CommitGraph: React.FC<CommitGraphProps> = React.memo(({
       if (!node) return null;
 
       const isSelected = commit.hash === selectedCommitHash;
-      const color = getBranchColor(node.lane);
+      const color = getBranchColor(node.color);
       const isMergeCommit = commit.parent_hashes.length > 1;
       const dotRadius = isSelected ? 6 : isMergeCommit ? 5 : 4;
 
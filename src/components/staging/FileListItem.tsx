@@ -1,5 +1,6 @@
 interface FileListItemProps {
   path: string;
+  oldPath?: string | null; // Source path of a rename
   status: string; // "modified", "added", "deleted", "renamed", "conflicted"
   isStaged: boolean;
   onClick: () => void;
@@ -22,7 +23,7 @@ function getStatusIcon(status: string): { icon: string; color: string; label: st
   };
 }
 
-export function FileListItem({ path, status, isStaged, onClick, onContextMenu }: FileListItemProps) {
+export function FileListItem({ path, oldPath, status, isStaged, onClick, onContextMenu }: FileListItemProps) {
   const statusInfo = getStatusIcon(status);
 
   return (
@@ -46,7 +47,7 @@ export function FileListItem({ path, status, isStaged, onClick, onContextMenu }:
 
         {/* File Path */}
         <span className="text-sm text-theme-primary font-mono truncate flex-1">
-          {path}
+          {oldPath ? `${oldPath} → ${path}` : path}
         </span>
 
         {/* Action Hint */}
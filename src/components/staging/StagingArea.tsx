@@ -5,6 +5,7 @@ import { CommitMessageInput } from './CommitMessageInput';
 
 interface WorkingDirectoryFile {
   path: string;
+  old_path: string | null;
   status: string;
   is_staged: boolean;
 }
@@ -25,6 +26,7 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [committing, setCommitting] = useState(false);
+  const [amend, setAmend] = useState(false);
   const [contextMenu, setContextMenu] = useState<{
     x: number;
     y: number;
@@ -106,12 +108,16 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
   const handleFileContextAction = (action: 'stage' | 'unstage' | 'discard' | 'copyPath') => {
     if (!contextMenu) return;
 
+    // Renames move both paths together
+    const { path, old_path } = contextMenu.file;
+    const paths = old_path ? [path, old_path] : [path];
+
     switch (action) {
       case 'stage':
-        handleStageFiles([contextMenu.file.path]);
+        handleStageFiles(paths);
         break;
       case 'unstage':
-        handleUnstageFiles([contextMenu.file.path]);
+        handleUnstageFiles(paths);
         break;
       case 'discard':
         const confirmed = confirm(
@@ -130,8 +136,45 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
     setContextMenu(null);
   };
 
+  // Replace the last commit with the staged changes (an empty message keeps the old one)
+  const handleAmend = async (allowPushed = false) => {
+    setCommitting(true);
+    setError(null);
+
+    try {
+      await invoke('amend_commit', {
+        path: repoPath,
+        message: commitMessage.trim() === '' ? null : commitMessage,
+        resetAuthor: false,
+        allowPushed,
+      });
+
+      setCommitMessage('');
+      setAmend(false);
+      await loadStatus();
+      onCommitCreated();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : String(err);
+      if (!allowPushed && message.includes('already been pushed')) {
+        setCommitting(false);
+        if (confirm(`${message}\n\nAmend anyway?`)) {
+          await handleAmend(true);
+        }
+        return;
+      }
+      setError(message);
+    } finally {
+      setCommitting(false);
+    }
+  };
+
   // Create commit
   const handleCommit = async () => {
+    if (amend) {
+      await handleAmend();
+      return;
+    }
+
     if (commitMessage.trim() === '') {
       setError('Commit message cannot be empty');
       return;
@@ -206,9 +249,12 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
                   <FileListItem
                     key={file.path}
                     path={file.path}
+                    oldPath={file.old_path}
                     status={file.status}
                     isStaged={false}
-                    onClick={() => handleStageFiles([file.path])}
+                    onClick={() =>
+                      handleStageFiles(file.old_path ? [file.path, file.old_path] : [file.path])
+                    }
                     onContextMenu={(x, y) => handleFileContextMenu(file, x, y)}
                   />
                 ))}
@@ -234,9 +280,12 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
                   <FileListItem
                     key={file.path}
                     path={file.path}
+                    oldPath={file.old_path}
                     status={file.status}
                     isStaged={true}
-                    onClick={() => handleUnstageFiles([file.path])}
+                    onClick={() =>
+                      handleUnstageFiles(file.old_path ? [file.path, file.old_path] : [file.path])
+                    }
                     onContextMenu={(x, y) => handleFileContextMenu(file, x, y)}
                   />
                 ))}
@@ -251,15 +300,25 @@ export function StagingArea({ repoPath, onCommitCreated }: StagingAreaProps) {
           value={commitMessage}
           onChange={setCommitMessage}
           onSubmit={handleCommit}
-          disabled={committing || status.staged.length === 0}
+          disabled={committing || (!amend && status.staged.length === 0)}
         />
 
+        <label className="flex items-center gap-2 mt-3 text-xs text-theme-secondary cursor-pointer select-none">
+          <input
+            type="checkbox"
+            checked={amend}
+            onChange={(e) => setAmend(e.target.checked)}
+            disabled={committing}
+          />
+          Amend last commit {amend && commitMessage.trim() === '' && '(keeps its message)'}
+        </label>
+
         <button
           onClick={handleCommit}
-          disabled={committing || status.staged.length === 0 || commitMessage.trim() === ''}
+          disabled={committing || (!amend && (status.staged.length === 0 || commitMessage.trim() === ''))}
           className="w-full mt-3 px-4 py-2 bg-graft-600 hover:bg-graft-700 active:bg-graft-800 disabled:bg-theme-surface disabled:text-theme-tertiary rounded-lg font-medium text-sm transition-all duration-200 focus:outline-none focus:ring-2 focus:ring-graft-500"
         >
-          {committing ? 'Committing...' : `Commit ${status.staged.length} file${status.staged.length !== 1 ? 's' : ''}`}
+          {committing ? 'Committing...' : amend ? 'Amend last commit' : `Commit ${status.staged.length} file${status.staged.length !== 1 ? 's' : ''}`}
         </button>
       </div>
 
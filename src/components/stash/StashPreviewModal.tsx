@@ -147,8 +147,16 @@ export function StashPreviewModal({
                       {icon}
                     </span>
                     <span className="text-sm text-theme-primary font-mono flex-1 min-w-0 truncate">
-                      {file.path}
+                      {file.old_path ? `${file.old_path} → ${file.path}` : file.path}
                     </span>
+                    {file.is_binary ? (
+                      <span className="text-xs text-theme-tertiary font-mono">binary</span>
+                    ) : (
+                      <span className="text-xs font-mono whitespace-nowrap">
+                        <span className="text-green-400">+{file.insertions}</span>{' '}
+                        <span className="text-red-400">−{file.deletions}</span>
+                      </span>
+                    )}
                   </div>
                 );
               })}
@@ -17,7 +17,9 @@ export interface StashCreateOptions {
 
 export interface FileChange {
   path: string;
+  old_path: string | null;
   status: string;
   insertions: number;
   deletions: number;
+  is_binary: boolean;
 }
@@ -19,7 +19,8 @@ describe('graphLayout performance', () => {
         timestamp: Date.now() - i * 1000,
         parent_hashes: i > 0 ? [`commit${i - 1}`.padEnd(40, '0')] : [],
         branches: [],
-        tags: []
+        tags: [],
+        refs: []
       });
     }
 
@@ -60,7 +61,8 @@ describe('graphLayout performance', () => {
         timestamp: Date.now() - i * 1000,
         parent_hashes: parentHashes,
         branches: [],
-        tags: []
+        tags: [],
+        refs: []
       });
     }
 
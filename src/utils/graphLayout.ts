@@ -15,6 +15,34 @@ export interface TagRef {
   name: string;
   is_annotated: boolean;
   is_remote: boolean;
+  // Only set for annotated tags
+  tagger_name?: string | null;
+  tagger_email?: string | null;
+  tagger_date?: number | null;
+  message?: string | null;
+}
+
+/**
+ * HEAD, stash entries, notes and custom namespaces such as refs/pull/*
+ */
+export interface RefDecoration {
+  name: string;
+  full_name: string;
+  kind: 'head' | 'stash' | 'note' | 'other';
+}
+
+/**
+ * Lane layout computed by the backend (`get_commits`), consistent across pages
+ */
+export interface CommitGraphNode {
+  lane: number;
+  color: number;
+  edges: Array<{
+    parent_hash: string;
+    to_lane: number;
+    color: number;
+    is_merge: boolean;
+  }>;
 }
 
 export interface Commit {
@@ -27,11 +55,14 @@ export interface Commit {
   parent_hashes: string[];
   branches: BranchRef[];
   tags: TagRef[];
+  refs: RefDecoration[];
+  graph?: CommitGraphNode | null;
 }
 
 export interface GraphNode {
   commitHash: string;
   lane: number;
+  color: number;
   x: number;
   y: number;
   parents: string[];
@@ -55,6 +86,11 @@ export interface GraphLayout {
  * 4. Reuse lanes when branches merge
  */
 export function calculateGraphLayout(commits: Commit[]): GraphLayout {
+  // Prefer the backend layout, which stays consistent across pages
+  if (commits.length > 0 && commits.every(commit => commit.graph)) {
+    return layoutFromBackend(commits);
+  }
+
   const nodes = new Map<string, GraphNode>();
   const laneMap = new Map<string, number>();
   let maxLane = 0;
@@ -134,6 +170,7 @@ export function calculateGraphLayout(commits: Commit[]): GraphLayout {
     nodes.set(commit.hash, {
       commitHash: commit.hash,
       lane,
+      color: lane,
       x,
       y,
       parents: commit.parent_hashes,
@@ -146,6 +183,30 @@ export function calculateGraphLayout(commits: Commit[]): GraphLayout {
   };
 }
 
+/**
+ * Build the layout from the lanes and colors computed by the backend
+ */
+function layoutFromBackend(commits: Commit[]): GraphLayout {
+  const nodes = new Map<string, GraphNode>();
+  let maxLane = 0;
+
+  commits.forEach((commit, index) => {
+    const graph = commit.graph!;
+    maxLane = Math.max(maxLane, graph.lane, ...graph.edges.map(edge => edge.to_lane));
+
+    nodes.set(commit.hash, {
+      commitHash: commit.hash,
+      lane: graph.lane,
+      color: graph.color,
+      x: graph.lane * 40,
+      y: index * 80,
+      parents: commit.parent_hashes,
+    });
+  });
+
+  return { nodes, maxLane };
+}
+
 /**
  * Find the first available lane (not in use)
  * O(k) where k = number of active lanes (typically <10)
@@ -169,11 +230,16 @@ export function getEdgePaths(
   from: GraphNode;
   to: GraphNode;
   type: 'parent' | 'merge';
+  color: number;
+  // Lane the line runs in between the two commits (backend layout only)
+  lane?: number;
 }> {
   const edges: Array<{
     from: GraphNode;
     to: GraphNode;
     type: 'parent' | 'merge';
+    color: number;
+    lane?: number;
   }> = [];
 
   const commitMap = new Map<string, Commit>();
@@ -191,10 +257,14 @@ export function getEdgePaths(
 
       // First parent is a regular parent, others are merge parents
       const isMerge = index > 0;
+      // The backend only sends edges to parents in the walk, so match by hash
+      const backendEdge = commit.graph?.edges.find(edge => edge.parent_hash === parentHash);
       edges.push({
         from: currentNode,
         to: parentNode,
         type: isMerge ? 'merge' : 'parent',
+        color: backendEdge?.color ?? (isMerge ? parentNode.color : currentNode.color),
+        lane: backendEdge?.to_lane,
       });
     });
   });
@@ -217,6 +287,6 @@ const BRANCH_COLORS = [
   '#f97316', // Orange
 ];
 
-export function getBranchColor(lane: number): string {
-  return BRANCH_COLORS[lane % BRANCH_COLORS.length];
+export function getBranchColor(color: number): string {
+  return BRANCH_COLORS[color % BRANCH_COLORS.length];
 }